@@ -2,7 +2,11 @@
 //!
 //! # Features
 //!
-//! * Can control the volume of your default input and output device.
+//! * Can control the volume of your default input and output device, or any device selected with `--device`.
+//! * Can control the volume of individual application playback and recording streams.
+//! * Can list the available output and input devices, and set the default output and input device.
+//! * Can print the current volume as plain text or JSON, for use in scripts and status bars.
+//! * Can watch for volume changes and show a notification whenever they happen.
 //! * Shows a notification with the new volume.
 //!   * Notifications include a progress bar if the notification daemon supports it!
 //!
@@ -14,9 +18,9 @@
 //! volume-ctl --help
 //! ```
 
-use libpulse_binding::context::{State, Context};
+use libpulse_binding::context::{State, Context, FlagSet as ContextFlagSet};
 use libpulse_binding::error::PAErr;
-use libpulse_binding::mainloop::standard::Mainloop;
+use libpulse_binding::mainloop::threaded::Mainloop;
 use libpulse_binding::volume::{ChannelVolumes, Volume};
 use std::sync::{Mutex, Arc};
 use notify_rust::Notification;
@@ -37,6 +41,13 @@ struct Options {
 	#[clap(action = clap::ArgAction::Count)]
 	quiet: u8,
 
+	/// The device to act on, by name.
+	///
+	/// Defaults to the default sink/source of the sound server.
+	#[clap(long, short)]
+	#[clap(global = true)]
+	device: Option<String>,
+
 	/// The command to execute.
 	#[clap(subcommand)]
 	command: Command,
@@ -54,7 +65,42 @@ enum Command {
 	Input {
 		#[clap(subcommand)]
 		command: VolumeCommand,
-	}
+	},
+
+	/// Control the volume of a single application stream.
+	App {
+		/// Control a recording stream instead of a playback stream.
+		#[clap(long)]
+		recording: bool,
+
+		/// Match the stream by index instead of by application name.
+		#[clap(long)]
+		index: bool,
+
+		/// The application name (or index, with `--index`) to match.
+		name: String,
+
+		#[clap(subcommand)]
+		command: VolumeCommand,
+	},
+
+	/// List all available output and input devices.
+	List,
+
+	/// Watch for volume/mute changes and show a notification whenever they occur.
+	Watch,
+
+	/// Set the default output device.
+	DefaultOutput {
+		/// The name of the device to use as the default output device.
+		name: String,
+	},
+
+	/// Set the default input device.
+	DefaultInput {
+		/// The name of the device to use as the default input device.
+		name: String,
+	},
 }
 
 #[derive(clap::Subcommand)]
@@ -83,6 +129,28 @@ enum VolumeCommand {
 	Mute,
 	/// Unmute the volume.
 	Unmute,
+	/// Print the current volume and mute state without changing anything.
+	Status {
+		/// The output format.
+		#[clap(long, value_enum, default_value_t = StatusFormat::Json)]
+		format: StatusFormat,
+	},
+}
+
+/// The output format for [`VolumeCommand::Status`].
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum StatusFormat {
+	/// Print the status as a JSON object.
+	Json,
+	/// Print the status as a plain `NN%` or `muted` line.
+	Plain,
+}
+
+impl std::fmt::Display for StatusFormat {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		use clap::ValueEnum;
+		self.to_possible_value().unwrap().get_name().fmt(f)
+	}
 }
 
 fn main() {
@@ -99,48 +167,374 @@ fn do_main(options: Options) -> Result<(), ()> {
 		.parse_default_env()
 		.init();
 
-	let mut main_loop = Mainloop::new()
-		.ok_or_else(|| eprintln!("Failed to initialize PulseAudio main loop."))?;
-	let context = connect(&mut main_loop)?;
+	let pulse = PulseLoop::connect()?;
 
+	let device = options.device.as_deref();
 	match options.command {
-		Command::Output { command } => run_output_command(&mut main_loop, &context, command),
-		Command::Input { command } => run_input_command(&mut main_loop, &context, command),
+		Command::Output { command } => run_output_command(&pulse, device, command),
+		Command::Input { command } => run_input_command(&pulse, device, command),
+		Command::App { recording, index, name, command } => run_app_command(&pulse, recording, index, &name, command),
+		Command::List => run_list_command(&pulse),
+		Command::Watch => run_watch_command(&pulse, device),
+		Command::DefaultOutput { name } => run_default_output_command(&pulse, &name),
+		Command::DefaultInput { name } => run_default_input_command(&pulse, &name),
+	}
+}
+
+/// A connection to a PulseAudio or PipeWire sound server, driven by a background thread.
+///
+/// Using a threaded main loop (rather than the synchronous `standard::Mainloop`) means the connection keeps
+/// dispatching events on its own thread, which is what makes [`run_watch_command`] possible in the first place.
+struct PulseLoop {
+	main_loop: Arc<Mainloop>,
+	context: Context,
+}
+
+impl PulseLoop {
+	/// Connect to a PulseAudio or PipeWire sound server, and start the background thread that drives it.
+	fn connect() -> Result<Self, ()> {
+		let main_loop = Mainloop::new()
+			.ok_or_else(|| eprintln!("Failed to initialize PulseAudio main loop."))?;
+		let main_loop = Arc::new(main_loop);
+
+		let mut context = Context::new(main_loop.as_ref(), "volume-control")
+			.ok_or_else(|| eprintln!("Failed initialize PulseAudio context."))?;
+		log::debug!("Protocol version: {}", context.get_protocol_version());
+
+		// Wake up the main thread whenever the context state changes, so the loop below can notice.
+		let wake_on_state_change = main_loop.clone();
+		context.set_state_callback(Some(Box::new(move || {
+			wake_on_state_change.signal(false);
+		})));
+
+		main_loop.lock();
+
+		context.connect(None, ContextFlagSet::NOFLAGS, None)
+			.map_err(|e| { main_loop.unlock(); eprintln!("Failed to connect to PulseAudio server: {e}"); })?;
+
+		main_loop.start()
+			.map_err(|e| { main_loop.unlock(); eprintln!("Failed to start PulseAudio main loop: {e}"); })?;
+
+		let result = loop {
+			let state = context.get_state();
+			log::debug!("Context state: {state:?}");
+			match state {
+				State::Ready => break Ok(()),
+				State::Failed => break Err(()),
+				State::Terminated => break Err(()),
+				State::Unconnected | State::Connecting | State::Authorizing | State::SettingName => {
+					main_loop.wait();
+				},
+			}
+		};
+
+		if result.is_err() {
+			log::error!("Failed to connect to PulseAudio server: {}", context.errno());
+		}
+
+		context.set_state_callback(None);
+		main_loop.unlock();
+		result?;
+
+		Ok(Self { main_loop, context })
+	}
+
+	/// Get the [`Context`] used to talk to the sound server.
+	fn context(&self) -> &Context {
+		&self.context
+	}
+
+	/// Get the most recent error reported by the context.
+	fn errno(&self) -> PAErr {
+		self.context.errno()
+	}
+
+	/// Run an operation against the context and block the calling thread until its result is available.
+	///
+	/// The main loop is locked for the duration of the call. The `operation` closure is handed a clone of the
+	/// [`Mainloop`] handle so that the PulseAudio callback it registers can call `signal` once `output` has been
+	/// filled in, waking this thread back up.
+	fn run<F, T>(&self, operation: F) -> T
+	where
+		F: FnOnce(&Context, Arc<Mainloop>, Arc<Mutex<Option<T>>>),
+	{
+		let output = Arc::new(Mutex::new(None));
+
+		self.main_loop.lock();
+		operation(&self.context, self.main_loop.clone(), output.clone());
+
+		let value = loop {
+			if let Some(value) = output.lock().unwrap().take() {
+				break value;
+			}
+			self.main_loop.wait();
+		};
+		self.main_loop.unlock();
+
+		value
+	}
+}
+
+impl Drop for PulseLoop {
+	fn drop(&mut self) {
+		self.main_loop.stop();
 	}
 }
 
 /// Run a volume command on the output device.
-fn run_output_command(main_loop: &mut Mainloop, context: &Context, command: VolumeCommand) -> Result<(), ()> {
-	let mut volumes = get_output_volumes(main_loop, context)
+fn run_output_command(pulse: &PulseLoop, device: Option<&str>, command: VolumeCommand) -> Result<(), ()> {
+	let mut volumes = get_output_volumes(pulse, device)
 		.map_err(|e| log::error!("Failed to get output volume: {e}"))?;
 
+	if let VolumeCommand::Status { format } = command {
+		return print_status(&volumes, format);
+	}
+
 	apply_volume_command(&mut volumes, &command);
 
-	set_output_volumes(main_loop, context, &volumes.channels)
+	set_output_volumes(pulse, device, &volumes.channels)
 		.map_err(|e| log::error!("Failed to set output volume: {e}"))?;
-	set_output_muted(main_loop, context, volumes.muted)
+	set_output_muted(pulse, device, volumes.muted)
 		.map_err(|e| log::error!("Failed to mute/unmute output volume: {e}"))?;
 
-	show_notification("Volume", "audio-volume", 0x49adff07, &volumes);
+	show_notification("Volume", "audio-volume", Some(0x49adff07), &volumes);
 
 	Ok(())
 }
 
 /// Run a volume command on the input device.
-fn run_input_command(main_loop: &mut Mainloop, context: &Context, command: VolumeCommand) -> Result<(), ()> {
-	let mut volumes = get_input_volumes(main_loop, context)
+fn run_input_command(pulse: &PulseLoop, device: Option<&str>, command: VolumeCommand) -> Result<(), ()> {
+	let mut volumes = get_input_volumes(pulse, device)
 		.map_err(|e| log::error!("Failed to get input volume: {e}"))?;
+
+	if let VolumeCommand::Status { format } = command {
+		return print_status(&volumes, format);
+	}
+
 	apply_volume_command(&mut volumes, &command);
-	set_input_volumes(main_loop, context, &volumes.channels)
+	set_input_volumes(pulse, device, &volumes.channels)
 		.map_err(|e| log::error!("Failed to set input volume: {e}"))?;
-	set_input_muted(main_loop, context, volumes.muted)
+	set_input_muted(pulse, device, volumes.muted)
 		.map_err(|e| log::error!("Failed to mute/unmute input volume: {e}"))?;
 
-	show_notification("Microphone", "microphone-sensitivity", 0x49adff08, &volumes);
+	show_notification("Microphone", "microphone-sensitivity", Some(0x49adff08), &volumes);
+
+	Ok(())
+}
+
+/// Run a volume command on a single application stream.
+fn run_app_command(pulse: &PulseLoop, recording: bool, by_index: bool, name: &str, command: VolumeCommand) -> Result<(), ()> {
+	if recording {
+		let streams = get_source_output_list(pulse)
+			.map_err(|e| log::error!("Failed to list recording streams: {e}"))?;
+		let stream = find_app_stream(&streams, name, by_index)
+			.ok_or_else(|| log::error!("No recording stream found matching `{name}`"))?;
+
+		let mut volumes = Volumes { name: stream.name.clone(), muted: stream.muted, channels: stream.channels.clone() };
+		if let VolumeCommand::Status { format } = command {
+			return print_status(&volumes, format);
+		}
+		apply_volume_command(&mut volumes, &command);
+
+		set_source_output_volumes(pulse, stream.index, &volumes.channels)
+			.map_err(|e| log::error!("Failed to set volume of recording stream {}: {e}", stream.index))?;
+		set_source_output_muted(pulse, stream.index, volumes.muted)
+			.map_err(|e| log::error!("Failed to mute/unmute recording stream {}: {e}", stream.index))?;
+
+		show_notification(&stream.name, "microphone-sensitivity", None, &volumes);
+	} else {
+		let streams = get_sink_input_list(pulse)
+			.map_err(|e| log::error!("Failed to list playback streams: {e}"))?;
+		let stream = find_app_stream(&streams, name, by_index)
+			.ok_or_else(|| log::error!("No playback stream found matching `{name}`"))?;
+
+		let mut volumes = Volumes { name: stream.name.clone(), muted: stream.muted, channels: stream.channels.clone() };
+		if let VolumeCommand::Status { format } = command {
+			return print_status(&volumes, format);
+		}
+		apply_volume_command(&mut volumes, &command);
+
+		set_sink_input_volumes(pulse, stream.index, &volumes.channels)
+			.map_err(|e| log::error!("Failed to set volume of playback stream {}: {e}", stream.index))?;
+		set_sink_input_muted(pulse, stream.index, volumes.muted)
+			.map_err(|e| log::error!("Failed to mute/unmute playback stream {}: {e}", stream.index))?;
+
+		show_notification(&stream.name, "audio-volume", None, &volumes);
+	}
+
+	Ok(())
+}
+
+/// Set the default output device of the sound server.
+fn run_default_output_command(pulse: &PulseLoop, name: &str) -> Result<(), ()> {
+	set_default_output(pulse, name)
+		.map_err(|e| log::error!("Failed to set default output device: {e}"))?;
+
+	show_simple_notification(&format!("Default output device: {name}"), "audio-volume-high");
+
+	Ok(())
+}
+
+/// Set the default input device of the sound server.
+fn run_default_input_command(pulse: &PulseLoop, name: &str) -> Result<(), ()> {
+	set_default_input(pulse, name)
+		.map_err(|e| log::error!("Failed to set default input device: {e}"))?;
+
+	show_simple_notification(&format!("Default input device: {name}"), "microphone-sensitivity-high");
+
+	Ok(())
+}
+
+/// Find the application stream matching `name`, either by index (if `by_index` is set) or by application name.
+fn find_app_stream<'a>(streams: &'a [AppStream], name: &str, by_index: bool) -> Option<&'a AppStream> {
+	if by_index {
+		let index: u32 = name.parse().ok()?;
+		streams.iter().find(|stream| stream.index == index)
+	} else {
+		streams.iter().find(|stream| stream.name.eq_ignore_ascii_case(name))
+	}
+}
+
+/// List all available output and input devices.
+fn run_list_command(pulse: &PulseLoop) -> Result<(), ()> {
+	let sinks = get_sink_list(pulse)
+		.map_err(|e| log::error!("Failed to list output devices: {e}"))?;
+	println!("Output devices:");
+	for sink in &sinks {
+		print_device_info(sink);
+	}
+
+	let sources = get_source_list(pulse)
+		.map_err(|e| log::error!("Failed to list input devices: {e}"))?;
+	println!("Input devices:");
+	for source in &sources {
+		print_device_info(source);
+	}
 
 	Ok(())
 }
 
+/// Print a single line describing a [`DeviceInfo`].
+fn print_device_info(device: &DeviceInfo) {
+	let state = if device.muted { "muted" } else { "unmuted" };
+	println!("  [{}] {} ({}): {:.0}%, {}", device.index, device.name, device.description, device.volume, state);
+}
+
+/// Watch for volume/mute changes on the output and input device and notify about them.
+///
+/// Watches the default devices, or the device named by `device` if it is given.
+fn run_watch_command(pulse: &PulseLoop, device: Option<&str>) -> Result<(), ()> {
+	use libpulse_binding::context::subscribe::{Facility, InterestMaskSet, Operation as SubscribeOperation};
+
+	let output_dirty = Arc::new(Mutex::new(false));
+	let input_dirty = Arc::new(Mutex::new(false));
+	let subscribed = Arc::new(Mutex::new(None));
+
+	{
+		let output_dirty = output_dirty.clone();
+		let input_dirty = input_dirty.clone();
+
+		pulse.main_loop.lock();
+		let main_loop = pulse.main_loop.clone();
+		pulse.context().set_subscribe_callback(Some(Box::new(move |facility, operation, _index| {
+			if operation != Some(SubscribeOperation::Changed) {
+				return;
+			}
+			match facility {
+				Some(Facility::Sink) | Some(Facility::Server) => *output_dirty.lock().unwrap() = true,
+				Some(Facility::Source) => *input_dirty.lock().unwrap() = true,
+				_ => (),
+			}
+			main_loop.signal(false);
+		})));
+
+		// `PulseLoop::connect` tears its own state callback down once the initial connection succeeds, so
+		// re-register one here for as long as we're watching, to notice if the server goes away.
+		let main_loop = pulse.main_loop.clone();
+		pulse.context().set_state_callback(Some(Box::new(move || {
+			main_loop.signal(false);
+		})));
+
+		let subscribed_result = subscribed.clone();
+		let main_loop = pulse.main_loop.clone();
+		pulse.context().subscribe(InterestMaskSet::SINK | InterestMaskSet::SOURCE | InterestMaskSet::SERVER, move |success| {
+			*subscribed_result.lock().unwrap() = Some(success);
+			main_loop.signal(false);
+		});
+		while subscribed.lock().unwrap().is_none() {
+			pulse.main_loop.wait();
+		}
+		let success = subscribed.lock().unwrap().take().unwrap();
+		pulse.main_loop.unlock();
+
+		if !success {
+			log::error!("Failed to subscribe to PulseAudio events: {}", pulse.errno());
+			return Err(());
+		}
+	}
+
+	let mut last_output = get_output_volumes(pulse, device).ok().as_ref().map(VolumeState::new);
+	let mut last_input = get_input_volumes(pulse, device).ok().as_ref().map(VolumeState::new);
+
+	log::info!("Watching for volume changes...");
+	loop {
+		pulse.main_loop.lock();
+		while !*output_dirty.lock().unwrap() && !*input_dirty.lock().unwrap() {
+			match pulse.context().get_state() {
+				State::Failed | State::Terminated => {
+					pulse.main_loop.unlock();
+					log::error!("Lost connection to PulseAudio server: {}", pulse.errno());
+					return Err(());
+				},
+				_ => (),
+			}
+			pulse.main_loop.wait();
+		}
+		let output_changed = std::mem::take(&mut *output_dirty.lock().unwrap());
+		let input_changed = std::mem::take(&mut *input_dirty.lock().unwrap());
+		pulse.main_loop.unlock();
+
+		if output_changed {
+			if let Ok(volumes) = get_output_volumes(pulse, device) {
+				let state = VolumeState::new(&volumes);
+				if last_output != Some(state) {
+					show_notification("Volume", "audio-volume", Some(0x49adff07), &volumes);
+					last_output = Some(state);
+				}
+			}
+		}
+
+		if input_changed {
+			if let Ok(volumes) = get_input_volumes(pulse, device) {
+				let state = VolumeState::new(&volumes);
+				if last_input != Some(state) {
+					show_notification("Microphone", "microphone-sensitivity", Some(0x49adff08), &volumes);
+					last_input = Some(state);
+				}
+			}
+		}
+	}
+}
+
+/// A condensed, comparable snapshot of a [`Volumes`] struct, used to detect actual changes.
+#[derive(Clone, Copy, PartialEq)]
+struct VolumeState {
+	/// Is the device muted?
+	muted: bool,
+	/// The rounded maximum channel volume, as a percentage.
+	volume: i64,
+}
+
+impl VolumeState {
+	/// Create a [`VolumeState`] from a [`Volumes`] struct.
+	fn new(volumes: &Volumes) -> Self {
+		Self {
+			muted: volumes.muted,
+			volume: volume_to_percentage(volumes.channels.max()).round() as i64,
+		}
+	}
+}
+
 /// Apply a [`VolumeCommand`] to a [`Volumes`] struct.
 fn apply_volume_command(volumes: &mut Volumes, command: &VolumeCommand) {
 	match command {
@@ -162,6 +556,9 @@ fn apply_volume_command(volumes: &mut Volumes, command: &VolumeCommand) {
 		VolumeCommand::ToggleMute => {
 			volumes.muted = !volumes.muted;
 		},
+		VolumeCommand::Status { .. } => {
+			unreachable!("status is handled before apply_volume_command is called");
+		},
 	}
 }
 
@@ -188,211 +585,377 @@ fn map_volumes<F: FnMut(f64) -> f64>(volumes: &mut ChannelVolumes, mut action: F
 
 /// Volume information for a input or output device.
 struct Volumes {
+	/// The name of the device.
+	name: String,
 	/// Is the device muted?
 	muted: bool,
 	/// The volumes of all channels of the device.
 	channels: ChannelVolumes,
 }
 
+/// Information about an available output or input device.
+struct DeviceInfo {
+	/// The index of the device.
+	index: u32,
+	/// The internal name of the device.
+	name: String,
+	/// The human readable description of the device.
+	description: String,
+	/// Is the device muted?
+	muted: bool,
+	/// The current volume of the device, as a percentage.
+	volume: f64,
+}
+
 /// Get the volume of the output device.
-fn get_output_volumes(main_loop: &mut Mainloop, context: &Context) -> Result<Volumes, PAErr> {
-	run(main_loop, move |output| {
-		context.introspect().get_sink_info_by_name("@DEFAULT_SINK@", move |info| {
+fn get_output_volumes(pulse: &PulseLoop, device: Option<&str>) -> Result<Volumes, PAErr> {
+	let device = device.unwrap_or("@DEFAULT_SINK@").to_owned();
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().get_sink_info_by_name(&device, move |info| {
 			match info {
 				libpulse_binding::callbacks::ListResult::Item(x) => {
 					*output.lock().unwrap() = Some(Ok(Volumes {
+						name: x.name.as_deref().unwrap_or_default().to_owned(),
 						muted: x.mute,
 						channels: x.volume,
 					}));
+					main_loop.signal(false);
 				},
 				libpulse_binding::callbacks::ListResult::End => {
 				},
 				libpulse_binding::callbacks::ListResult::Error => {
 					*output.lock().unwrap() = Some(Err(()));
+					main_loop.signal(false);
 				},
 			}
 		});
-	})?
-	.map_err(|()| context.errno())
+	})
+	.map_err(|()| pulse.errno())
 }
 
 /// Get the volume of the output device.
-fn set_output_volumes(main_loop: &mut Mainloop, context: &Context, volumes: &ChannelVolumes) -> Result<(), PAErr> {
-	run(main_loop, move |output| {
-		context.introspect().set_sink_volume_by_name("@DEFAULT_SINK@", volumes, Some(Box::new(move |success| {
-			if success {
-				*output.lock().unwrap() = Some(Ok(()));
-			} else {
-				*output.lock().unwrap() = Some(Err(()));
-			}
+fn set_output_volumes(pulse: &PulseLoop, device: Option<&str>, volumes: &ChannelVolumes) -> Result<(), PAErr> {
+	let device = device.unwrap_or("@DEFAULT_SINK@").to_owned();
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().set_sink_volume_by_name(&device, volumes, Some(Box::new(move |success| {
+			*output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+			main_loop.signal(false);
 		})));
-	})?
-	.map_err(|()| context.errno())
+	})
+	.map_err(|()| pulse.errno())
 }
 
 /// Set the muted state of the output device.
-fn set_output_muted(main_loop: &mut Mainloop, context: &Context, muted: bool) -> Result<(), PAErr> {
-	run(main_loop, move |output| {
-		context.introspect().set_sink_mute_by_name("@DEFAULT_SINK@", muted, Some(Box::new(move |success| {
-			if success {
-				*output.lock().unwrap() = Some(Ok(()));
-			} else {
-				*output.lock().unwrap() = Some(Err(()));
-			}
+fn set_output_muted(pulse: &PulseLoop, device: Option<&str>, muted: bool) -> Result<(), PAErr> {
+	let device = device.unwrap_or("@DEFAULT_SINK@").to_owned();
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().set_sink_mute_by_name(&device, muted, Some(Box::new(move |success| {
+			*output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+			main_loop.signal(false);
 		})));
-	})?
-	.map_err(|()| context.errno())
+	})
+	.map_err(|()| pulse.errno())
 }
 
 /// Get the volume of the input device.
-fn get_input_volumes(main_loop: &mut Mainloop, context: &Context) -> Result<Volumes, PAErr> {
-	run(main_loop, move |output| {
-		context.introspect().get_source_info_by_name("@DEFAULT_SOURCE@", move |info| {
+fn get_input_volumes(pulse: &PulseLoop, device: Option<&str>) -> Result<Volumes, PAErr> {
+	let device = device.unwrap_or("@DEFAULT_SOURCE@").to_owned();
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().get_source_info_by_name(&device, move |info| {
 			match info {
 				libpulse_binding::callbacks::ListResult::Item(x) => {
 					*output.lock().unwrap() = Some(Ok(Volumes {
+						name: x.name.as_deref().unwrap_or_default().to_owned(),
 						muted: x.mute,
 						channels: x.volume,
 					}));
+					main_loop.signal(false);
 				},
 				libpulse_binding::callbacks::ListResult::End => {
 				},
 				libpulse_binding::callbacks::ListResult::Error => {
 					*output.lock().unwrap() = Some(Err(()));
+					main_loop.signal(false);
 				},
 			}
 		});
-	})?
-	.map_err(|()| context.errno())
+	})
+	.map_err(|()| pulse.errno())
 }
 
 /// Set the volume of the input device.
-fn set_input_volumes(main_loop: &mut Mainloop, context: &Context, volumes: &ChannelVolumes) -> Result<(), PAErr> {
-	run(main_loop, move |output| {
-		context.introspect().set_source_volume_by_name("@DEFAULT_SOURCE@", volumes, Some(Box::new(move |success| {
-			if success {
-				*output.lock().unwrap() = Some(Ok(()));
-			} else {
-				*output.lock().unwrap() = Some(Err(()));
-			}
+fn set_input_volumes(pulse: &PulseLoop, device: Option<&str>, volumes: &ChannelVolumes) -> Result<(), PAErr> {
+	let device = device.unwrap_or("@DEFAULT_SOURCE@").to_owned();
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().set_source_volume_by_name(&device, volumes, Some(Box::new(move |success| {
+			*output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+			main_loop.signal(false);
 		})));
-	})?
-	.map_err(|()| context.errno())
+	})
+	.map_err(|()| pulse.errno())
 }
 
 /// Set the muted state of the input device.
-fn set_input_muted(main_loop: &mut Mainloop, context: &Context, muted: bool) -> Result<(), PAErr> {
-	run(main_loop, move |output| {
-		context.introspect().set_source_mute_by_name("@DEFAULT_SOURCE@", muted, Some(Box::new(move |success| {
-			if success {
-				*output.lock().unwrap() = Some(Ok(()));
-			} else {
-				*output.lock().unwrap() = Some(Err(()));
+fn set_input_muted(pulse: &PulseLoop, device: Option<&str>, muted: bool) -> Result<(), PAErr> {
+	let device = device.unwrap_or("@DEFAULT_SOURCE@").to_owned();
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().set_source_mute_by_name(&device, muted, Some(Box::new(move |success| {
+			*output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+			main_loop.signal(false);
+		})));
+	})
+	.map_err(|()| pulse.errno())
+}
+
+/// Set the default output device (sink) of the sound server.
+fn set_default_output(pulse: &PulseLoop, name: &str) -> Result<(), PAErr> {
+	let name = name.to_owned();
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().set_default_sink(&name, Some(Box::new(move |success| {
+			*output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+			main_loop.signal(false);
+		})));
+	})
+	.map_err(|()| pulse.errno())
+}
+
+/// Set the default input device (source) of the sound server.
+fn set_default_input(pulse: &PulseLoop, name: &str) -> Result<(), PAErr> {
+	let name = name.to_owned();
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().set_default_source(&name, Some(Box::new(move |success| {
+			*output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+			main_loop.signal(false);
+		})));
+	})
+	.map_err(|()| pulse.errno())
+}
+
+/// Information about a single application playback or recording stream.
+struct AppStream {
+	/// The index of the stream.
+	index: u32,
+	/// The application name, taken from the `application.name` property if available.
+	name: String,
+	/// Is the stream muted?
+	muted: bool,
+	/// The volumes of all channels of the stream.
+	channels: ChannelVolumes,
+}
+
+/// Get the list of all sink input streams (application playback streams).
+fn get_sink_input_list(pulse: &PulseLoop) -> Result<Vec<AppStream>, PAErr> {
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().get_sink_input_info_list(move |info| {
+			match info {
+				libpulse_binding::callbacks::ListResult::Item(x) => {
+					output.lock().unwrap().get_or_insert_with(|| Ok(Vec::new())).as_mut().unwrap().push(AppStream {
+						index: x.index,
+						name: app_name(&x.proplist, x.name.as_deref()),
+						muted: x.mute,
+						channels: x.volume,
+					});
+				},
+				libpulse_binding::callbacks::ListResult::End => {
+					output.lock().unwrap().get_or_insert_with(|| Ok(Vec::new()));
+					main_loop.signal(false);
+				},
+				libpulse_binding::callbacks::ListResult::Error => {
+					*output.lock().unwrap() = Some(Err(()));
+					main_loop.signal(false);
+				},
 			}
+		});
+	})
+	.map_err(|()| pulse.errno())
+}
+
+/// Set the volume of a sink input stream by index.
+fn set_sink_input_volumes(pulse: &PulseLoop, index: u32, volumes: &ChannelVolumes) -> Result<(), PAErr> {
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().set_sink_input_volume(index, volumes, Some(Box::new(move |success| {
+			*output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+			main_loop.signal(false);
 		})));
-	})?
-	.map_err(|()| context.errno())
-}
-
-/// Connect to a PulseAudio or PipeWire sound server.
-fn connect(main_loop: &mut Mainloop) -> Result<Context, ()> {
-	// Create the context.
-	let mut context = libpulse_binding::context::Context::new(main_loop, "volume-control")
-		.ok_or_else(|| eprintln!("Failed initialize PulseAudio context."))?;
-	log::debug!("Protocol version: {}", context.get_protocol_version());
-	log::debug!("Context state: {:?}", context.get_state());
-
-	// Initiate the connection.
-	context.connect(None, libpulse_binding::context::FlagSet::NOFLAGS, None)
-		.map_err(|e| eprintln!("Failed to connect to PulseAudio server: {e}"))?;
-	log::debug!("Context state: {:?}", context.get_state());
-
-	// Run the main loop until the connection succeeded or failed.
-	run_until(main_loop, |_main_loop| {
-		let state = context.get_state();
-		log::debug!("Context state: {:?}", state);
-		match state {
-			State::Ready => true,
-			State::Failed => true,
-			State::Unconnected => true,
-			State::Terminated => true,
-			State::Connecting => false,
-			State::Authorizing => false,
-			State::SettingName => false,
-		}
 	})
-	.map_err(|e| log::error!("Error in PulseAudio main loop: {e}"))?;
+	.map_err(|()| pulse.errno())
+}
 
-	// Check the end state to see if we connected successfully.
-	let state = context.get_state();
-	match state {
-		State::Ready => (),
-		State::Failed => {
-			log::error!("Failed to connect to PulseAudio server: {}", context.errno());
-			return Err(());
-		},
-		| State::Unconnected
-		| State::Terminated
-		| State::Connecting
-		| State::Authorizing
-		| State::SettingName => {
-			log::error!("PulseAudio context in unexpected state: {state:?}");
-			log::error!("Last error: {}", context.errno());
-			return Err(());
-		}
-	}
-	Ok(context)
+/// Set the muted state of a sink input stream by index.
+fn set_sink_input_muted(pulse: &PulseLoop, index: u32, muted: bool) -> Result<(), PAErr> {
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().set_sink_input_mute(index, muted, Some(Box::new(move |success| {
+			*output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+			main_loop.signal(false);
+		})));
+	})
+	.map_err(|()| pulse.errno())
 }
 
-/// Run the libpulse main loop until a condition becomes true.
-fn run_until<F>(main_loop: &mut Mainloop, condition: F) -> Result<Option<i32>, PAErr>
-where
-	F: Fn(&mut Mainloop) -> bool,
-{
-	use libpulse_binding::mainloop::standard::IterateResult;
-	loop {
-		match main_loop.iterate(true) {
-			IterateResult::Err(e) => {
-				return Err(e);
-			},
-			IterateResult::Quit(code) => {
-				return Ok(Some(code.0));
-			},
-			IterateResult::Success(_iterations) => (),
-		}
-		if condition(main_loop) {
-			return Ok(None)
-		};
-	}
+/// Get the list of all source output streams (application recording streams).
+fn get_source_output_list(pulse: &PulseLoop) -> Result<Vec<AppStream>, PAErr> {
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().get_source_output_info_list(move |info| {
+			match info {
+				libpulse_binding::callbacks::ListResult::Item(x) => {
+					output.lock().unwrap().get_or_insert_with(|| Ok(Vec::new())).as_mut().unwrap().push(AppStream {
+						index: x.index,
+						name: app_name(&x.proplist, x.name.as_deref()),
+						muted: x.mute,
+						channels: x.volume,
+					});
+				},
+				libpulse_binding::callbacks::ListResult::End => {
+					output.lock().unwrap().get_or_insert_with(|| Ok(Vec::new()));
+					main_loop.signal(false);
+				},
+				libpulse_binding::callbacks::ListResult::Error => {
+					*output.lock().unwrap() = Some(Err(()));
+					main_loop.signal(false);
+				},
+			}
+		});
+	})
+	.map_err(|()| pulse.errno())
 }
 
-/// Run the libpulse main loop until a value is set.
-fn run<F, T>(main_loop: &mut Mainloop, operation: F) -> Result<T, PAErr>
-where
-	F: FnOnce(Arc<Mutex<Option<T>>>),
-{
-	use libpulse_binding::mainloop::standard::IterateResult;
-	let output = Arc::new(Mutex::new(None));
-	operation(output.clone());
+/// Set the volume of a source output stream by index.
+fn set_source_output_volumes(pulse: &PulseLoop, index: u32, volumes: &ChannelVolumes) -> Result<(), PAErr> {
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().set_source_output_volume(index, volumes, Some(Box::new(move |success| {
+			*output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+			main_loop.signal(false);
+		})));
+	})
+	.map_err(|()| pulse.errno())
+}
 
-	loop {
-		if let Some(value) = output.lock().unwrap().take() {
-			return Ok(value);
-		}
-		match main_loop.iterate(true) {
-			IterateResult::Err(e) => {
-				return Err(e);
-			},
-			IterateResult::Quit(code) => {
-				std::process::exit(code.0);
-			},
-			IterateResult::Success(_iterations) => (),
-		}
+/// Set the muted state of a source output stream by index.
+fn set_source_output_muted(pulse: &PulseLoop, index: u32, muted: bool) -> Result<(), PAErr> {
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().set_source_output_mute(index, muted, Some(Box::new(move |success| {
+			*output.lock().unwrap() = Some(if success { Ok(()) } else { Err(()) });
+			main_loop.signal(false);
+		})));
+	})
+	.map_err(|()| pulse.errno())
+}
+
+/// Determine the display name for an application stream from its proplist, falling back to the stream name.
+///
+/// Returns an empty string if neither is available.
+fn app_name(proplist: &libpulse_binding::proplist::Proplist, stream_name: Option<&str>) -> String {
+	proplist.get_str("application.name")
+		.or_else(|| stream_name.map(str::to_owned))
+		.unwrap_or_default()
+}
+
+/// Get the list of all available output devices (sinks).
+fn get_sink_list(pulse: &PulseLoop) -> Result<Vec<DeviceInfo>, PAErr> {
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().get_sink_info_list(move |info| {
+			match info {
+				libpulse_binding::callbacks::ListResult::Item(x) => {
+					output.lock().unwrap().get_or_insert_with(|| Ok(Vec::new())).as_mut().unwrap().push(DeviceInfo {
+						index: x.index,
+						name: x.name.as_deref().unwrap_or_default().to_owned(),
+						description: x.description.as_deref().unwrap_or_default().to_owned(),
+						muted: x.mute,
+						volume: volume_to_percentage(x.volume.max()),
+					});
+				},
+				libpulse_binding::callbacks::ListResult::End => {
+					output.lock().unwrap().get_or_insert_with(|| Ok(Vec::new()));
+					main_loop.signal(false);
+				},
+				libpulse_binding::callbacks::ListResult::Error => {
+					*output.lock().unwrap() = Some(Err(()));
+					main_loop.signal(false);
+				},
+			}
+		});
+	})
+	.map_err(|()| pulse.errno())
+}
+
+/// Get the list of all available input devices (sources).
+fn get_source_list(pulse: &PulseLoop) -> Result<Vec<DeviceInfo>, PAErr> {
+	pulse.run(move |context, main_loop, output| {
+		context.introspect().get_source_info_list(move |info| {
+			match info {
+				libpulse_binding::callbacks::ListResult::Item(x) => {
+					output.lock().unwrap().get_or_insert_with(|| Ok(Vec::new())).as_mut().unwrap().push(DeviceInfo {
+						index: x.index,
+						name: x.name.as_deref().unwrap_or_default().to_owned(),
+						description: x.description.as_deref().unwrap_or_default().to_owned(),
+						muted: x.mute,
+						volume: volume_to_percentage(x.volume.max()),
+					});
+				},
+				libpulse_binding::callbacks::ListResult::End => {
+					output.lock().unwrap().get_or_insert_with(|| Ok(Vec::new()));
+					main_loop.signal(false);
+				},
+				libpulse_binding::callbacks::ListResult::Error => {
+					*output.lock().unwrap() = Some(Err(()));
+					main_loop.signal(false);
+				},
+			}
+		});
+	})
+	.map_err(|()| pulse.errno())
+}
+
+/// Machine readable snapshot of a device's volume/mute state, for the `status` subcommand.
+#[derive(serde::Serialize)]
+struct Status {
+	/// The name of the device.
+	device: String,
+	/// Is the device muted?
+	muted: bool,
+	/// The maximum channel volume, as a percentage.
+	volume: f64,
+	/// The volume of each individual channel, as a percentage.
+	channels: Vec<f64>,
+}
+
+/// Print the current volume/mute state of `volumes` in the given format, instead of changing anything.
+fn print_status(volumes: &Volumes, format: StatusFormat) -> Result<(), ()> {
+	match format {
+		StatusFormat::Json => {
+			let status = Status {
+				device: volumes.name.clone(),
+				muted: volumes.muted,
+				volume: volume_to_percentage(volumes.channels.max()),
+				channels: volumes.channels.get().iter().copied().map(volume_to_percentage).collect(),
+			};
+			let json = serde_json::to_string(&status)
+				.map_err(|e| log::error!("Failed to serialize status: {e}"))?;
+			println!("{json}");
+		},
+		StatusFormat::Plain => {
+			if volumes.muted {
+				println!("muted");
+			} else {
+				println!("{:.0}%", volume_to_percentage(volumes.channels.max()));
+			}
+		},
 	}
+	Ok(())
+}
+
+/// Show a simple notification with just a summary and an icon.
+fn show_simple_notification(summary: &str, icon: &str) {
+	Notification::new()
+		.summary(summary)
+		.icon(icon)
+		.show()
+		.map_err(|e| log::warn!("Failed to show notification: {e}"))
+		.ok();
 }
 
 /// Show a notification about the new sound server state.
-fn show_notification(name: &str, icon_prefix: &str, id: u32, volumes: &Volumes) {
+fn show_notification(name: &str, icon_prefix: &str, id: Option<u32>, volumes: &Volumes) {
 	let max_volume = volume_to_percentage(volumes.channels.max());
 	let mut notification = Notification::new();
 	if volumes.muted {
@@ -409,7 +972,9 @@ fn show_notification(name: &str, icon_prefix: &str, id: u32, volumes: &Volumes)
 	} else {
 		notification.icon(&format!("{icon_prefix}-high"));
 	}
-	notification.id(id);
+	if let Some(id) = id {
+		notification.id(id);
+	}
 	notification.hint(notify_rust::Hint::CustomInt("value".to_owned(), max_volume.round() as i32));
 	notification.show()
 		.map_err(|e| log::warn!("Failed to show notification: {e}"))